@@ -0,0 +1,172 @@
+use std::{
+    io::{Cursor, Read},
+    path::{Path, PathBuf},
+};
+
+use crate::GrepError;
+
+/// Converts a file's raw bytes into searchable text before the matcher ever
+/// sees it, so binary formats (PDF, zip, ...) can be grepped like plain text.
+pub trait FileAdapter: Send + Sync {
+    /// Short name used for diagnostics.
+    fn name(&self) -> &'static str;
+    /// Does this adapter claim `path`, based on its extension?
+    fn matches(&self, path: &Path) -> bool;
+    /// Does this adapter claim content starting with `header`? Used for
+    /// `--rga-accurate` content sniffing instead of extension matching.
+    fn sniff(&self, header: &[u8]) -> bool {
+        let _ = header;
+        false
+    }
+    /// Expand `path`'s raw bytes into one or more virtual (path, text)
+    /// entries to search. Most adapters produce exactly one entry; an
+    /// archive adapter can expand into one entry per member.
+    fn adapt(&self, path: &Path, contents: &[u8]) -> Result<AdaptedEntries, GrepError>;
+}
+
+/// Virtual (path, text) entries an adapter expands a file into.
+pub type AdaptedEntries = Vec<(PathBuf, Vec<u8>)>;
+
+/// A small, best-effort PDF text extractor: scans for `(...)` text-showing
+/// operands in the content stream and concatenates them, one per line. It
+/// does not inflate FlateDecode-compressed streams, so most real-world
+/// (compressed) PDFs will yield little or no text.
+pub struct PdfAdapter;
+
+impl FileAdapter for PdfAdapter {
+    fn name(&self) -> &'static str {
+        "pdf"
+    }
+
+    fn matches(&self, path: &Path) -> bool {
+        path.extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("pdf"))
+    }
+
+    fn sniff(&self, header: &[u8]) -> bool {
+        header.starts_with(b"%PDF-")
+    }
+
+    fn adapt(&self, path: &Path, contents: &[u8]) -> Result<AdaptedEntries, GrepError> {
+        Ok(vec![(
+            path.to_path_buf(),
+            extract_pdf_text(contents).into_bytes(),
+        )])
+    }
+}
+
+fn extract_pdf_text(raw: &[u8]) -> String {
+    let mut out = String::new();
+    let mut i = 0;
+    while i < raw.len() {
+        if raw[i] == b'(' {
+            let start = i + 1;
+            let mut depth = 1;
+            let mut j = start;
+            while j < raw.len() && depth > 0 {
+                match raw[j] {
+                    b'(' => depth += 1,
+                    b')' if raw[j - 1] != b'\\' => depth -= 1,
+                    _ => {}
+                }
+                j += 1;
+            }
+            if depth == 0 {
+                out.push_str(&String::from_utf8_lossy(&raw[start..j - 1]));
+                out.push('\n');
+            }
+            i = j;
+        } else {
+            i += 1;
+        }
+    }
+    out
+}
+
+/// Adapter for zip archives: yields each non-directory entry as a virtual
+/// sub-path (`archive.zip/inner.txt`) so it can be searched like any other
+/// file.
+pub struct ZipAdapter;
+
+impl FileAdapter for ZipAdapter {
+    fn name(&self) -> &'static str {
+        "zip"
+    }
+
+    fn matches(&self, path: &Path) -> bool {
+        path.extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"))
+    }
+
+    fn sniff(&self, header: &[u8]) -> bool {
+        header.starts_with(b"PK\x03\x04")
+    }
+
+    fn adapt(&self, path: &Path, contents: &[u8]) -> Result<AdaptedEntries, GrepError> {
+        let mut archive = zip::ZipArchive::new(Cursor::new(contents))?;
+        let mut entries = Vec::with_capacity(archive.len());
+        for i in 0..archive.len() {
+            let mut entry = archive.by_index(i)?;
+            if entry.is_dir() {
+                continue;
+            }
+            let mut buf = Vec::new();
+            entry.read_to_end(&mut buf)?;
+            entries.push((path.join(entry.name()), buf));
+        }
+        Ok(entries)
+    }
+}
+
+/// Selects and runs the `FileAdapter` (if any) that claims a given file.
+/// Consulted by `GrepConfig` per file before handing the reader to the
+/// matcher.
+pub struct AdapterRegistry {
+    adapters: Vec<Box<dyn FileAdapter>>,
+}
+
+impl Default for AdapterRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AdapterRegistry {
+    pub fn new() -> Self {
+        Self {
+            adapters: vec![Box::new(PdfAdapter), Box::new(ZipAdapter)],
+        }
+    }
+
+    fn select(&self, path: &Path, accurate: bool) -> Option<&dyn FileAdapter> {
+        if accurate {
+            let mut file = std::fs::File::open(path).ok()?;
+            let mut header = [0u8; 16];
+            let n = file.read(&mut header).ok()?;
+            self.adapters
+                .iter()
+                .find(|a| a.sniff(&header[..n]))
+                .map(|a| a.as_ref())
+        } else {
+            self.adapters
+                .iter()
+                .find(|a| a.matches(path))
+                .map(|a| a.as_ref())
+        }
+    }
+
+    /// Run the matched adapter against `path`, expanding it into one or more
+    /// virtual (path, text) entries. Returns `None` when no adapter claims
+    /// the file, meaning it should be searched unadapted as before.
+    pub fn adapt(
+        &self,
+        path: &Path,
+        accurate: bool,
+    ) -> Result<Option<AdaptedEntries>, GrepError> {
+        let Some(adapter) = self.select(path, accurate) else {
+            return Ok(None);
+        };
+        let contents = std::fs::read(path)?;
+        Ok(Some(adapter.adapt(path, &contents)?))
+    }
+}
@@ -1,25 +1,99 @@
 use clap::Parser;
 use colored::*;
 use futures::{Future, StreamExt};
-use itertools::Itertools;
+use ignore::{overrides::OverrideBuilder, types::TypesBuilder, WalkBuilder};
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use regex::Regex;
 use std::{
+    collections::VecDeque,
     fs::File,
     io::{self, BufRead, BufReader, Write},
     ops::Range,
     path::{Path, PathBuf},
     pin::Pin,
+    process::Stdio,
+    task::{Context, Poll},
 };
 use tokio::{
     fs,
-    io::{AsyncBufReadExt, AsyncWriteExt},
+    io::{AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncWriteExt, ReadBuf},
 };
 
 mod error;
 pub use error::GrepError;
 
-pub type StrategyFn = fn(&Path, &mut dyn BufRead, &Regex, &mut dyn Write) -> Result<(), GrepError>;
+mod printer;
+pub use printer::{OutputFormat, Printer, SummaryMode};
+
+mod adapter;
+pub use adapter::{AdapterRegistry, FileAdapter};
+
+pub type StrategyFn =
+    fn(&Path, &mut dyn BufRead, &Matcher, &mut Printer, ContextLines) -> Result<(), GrepError>;
+
+/// Number of surrounding lines to print around each match, grep-style.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ContextLines {
+    pub before: usize,
+    pub after: usize,
+}
+
+/// A pattern matcher backed by either the `regex` crate (fast, no
+/// look-around/backreferences) or PCRE2 (slower, supports the full PCRE
+/// syntax). Built once per `GrepConfig` and shared by every strategy call.
+#[derive(Debug, Clone)]
+pub enum Matcher {
+    Regex(Regex),
+    Pcre2(pcre2::bytes::Regex),
+}
+
+impl Matcher {
+    pub fn find(&self, line: &str) -> Option<Range<usize>> {
+        match self {
+            Matcher::Regex(r) => r.find(line).map(|m| m.range()),
+            Matcher::Pcre2(r) => match r.find(line.as_bytes()) {
+                Ok(m) => m.map(|m| m.start()..m.end()),
+                Err(e) => {
+                    eprintln!("debug: pcre2 match error: {e}");
+                    None
+                }
+            },
+        }
+    }
+
+    /// Every match occurrence on `line`, in order. Used so `--count-matches`
+    /// and JSON submatches stay consistent with each other instead of each
+    /// re-deriving their own notion of "how many hits on this line".
+    pub fn find_iter(&self, line: &str) -> Vec<Range<usize>> {
+        match self {
+            Matcher::Regex(r) => r.find_iter(line).map(|m| m.range()).collect(),
+            Matcher::Pcre2(r) => r
+                .find_iter(line.as_bytes())
+                .filter_map(|m| match m {
+                    Ok(m) => Some(m.start()..m.end()),
+                    Err(e) => {
+                        eprintln!("debug: pcre2 match error: {e}");
+                        None
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    /// Total number of match occurrences on `line`, for `--count-matches`.
+    pub fn count_occurrences(&self, line: &str) -> usize {
+        self.find_iter(line).len()
+    }
+}
+
+/// Per-file outcome of consulting the `AdapterRegistry` in `async_grep_with`:
+/// either a matched adapter's expanded entries, a plain reader when no
+/// adapter claimed the file, or an error from opening/adapting it.
+enum AdaptedSource {
+    Entries(Vec<(PathBuf, Vec<u8>)>),
+    Reader(Option<Pin<Box<dyn tokio::io::AsyncBufRead>>>),
+    Error(GrepError),
+}
 
 /// mini grep
 #[derive(Parser, Debug)]
@@ -27,27 +101,207 @@ pub type StrategyFn = fn(&Path, &mut dyn BufRead, &Regex, &mut dyn Write) -> Res
 pub struct GrepConfig {
     /// A regular expression used for searching
     pattern: String,
-    /// A pattern used during the search of the input
-    glob: String,
+    /// Directory or file to search, recursively
+    #[clap(default_value = ".")]
+    path: PathBuf,
+    /// A glob pattern used to filter the files that are searched
+    #[clap(short = 'g', long = "glob")]
+    glob: Option<String>,
+    /// Don't respect .gitignore, .ignore, or git's global excludes
+    #[clap(long = "no-ignore")]
+    no_ignore: bool,
+    /// Search hidden files and directories
+    #[clap(long = "hidden")]
+    hidden: bool,
+    /// Only search files matching the given type (e.g. rust, py, md)
+    #[clap(short = 't', long = "type")]
+    type_matches: Vec<String>,
+    /// Exclude files matching the given type
+    #[clap(long = "type-not")]
+    type_not: Vec<String>,
+    /// Print num lines of trailing context after each match
+    #[clap(short = 'A', long = "after-context", default_value_t = 0)]
+    after_context: usize,
+    /// Print num lines of leading context before each match
+    #[clap(short = 'B', long = "before-context", default_value_t = 0)]
+    before_context: usize,
+    /// Print num lines of both leading and trailing context around each match
+    #[clap(short = 'C', long = "context")]
+    context: Option<usize>,
+    /// Print matches as newline-delimited JSON instead of the colored text format
+    #[clap(long = "json")]
+    json: bool,
+    /// Treat the pattern as a literal string instead of a regular expression
+    #[clap(short = 'F', long = "fixed-strings")]
+    fixed_strings: bool,
+    /// Use PCRE2 instead of the regex crate, enabling look-around and
+    /// backreferences at the cost of search speed; not available for every
+    /// pattern that the regex crate accepts
+    #[clap(short = 'P', long = "pcre2")]
+    pcre2: bool,
+    /// Transparently decompress .gz/.bz2/.xz/.zst files before searching them
+    #[clap(short = 'z', long = "search-zip")]
+    search_zip: bool,
+    /// Detect adaptable file types (PDF, zip, ...) by sniffing their content
+    /// instead of relying on the file extension
+    #[clap(long = "rga-accurate")]
+    rga_accurate: bool,
+    /// Print `path:N`, the number of matching lines, instead of the matches
+    #[clap(short = 'c', long = "count")]
+    count: bool,
+    /// Print `path:N`, the total number of match occurrences (a line with
+    /// multiple hits counts more than once)
+    #[clap(long = "count-matches")]
+    count_matches: bool,
+    /// Print only the paths of files containing at least one match
+    #[clap(short = 'l', long = "files-with-matches")]
+    files_with_matches: bool,
 }
 
 impl GrepConfig {
+    fn matcher(&self) -> Result<Matcher, GrepError> {
+        let pattern = if self.fixed_strings {
+            regex::escape(&self.pattern)
+        } else {
+            self.pattern.clone()
+        };
+        if self.pcre2 {
+            Ok(Matcher::Pcre2(
+                pcre2::bytes::RegexBuilder::new()
+                    .utf(true)
+                    .build(&pattern)?,
+            ))
+        } else {
+            Ok(Matcher::Regex(Regex::new(&pattern)?))
+        }
+    }
+
+    fn context_lines(&self) -> ContextLines {
+        let context = self.context.unwrap_or(0);
+        ContextLines {
+            before: self.before_context.max(context),
+            after: self.after_context.max(context),
+        }
+    }
+
+    fn output_format(&self) -> OutputFormat {
+        if self.json {
+            OutputFormat::Json
+        } else {
+            OutputFormat::Text
+        }
+    }
+
+    fn summary_mode(&self) -> SummaryMode {
+        if self.files_with_matches {
+            SummaryMode::FilesWithMatches
+        } else if self.count_matches {
+            SummaryMode::CountMatches
+        } else if self.count {
+            SummaryMode::Count
+        } else {
+            SummaryMode::None
+        }
+    }
+
+    /// Recursively walk `self.path`, honoring `.gitignore`/`.ignore`/global
+    /// excludes (unless `--no-ignore` is set) and the `--type`/`--type-not`
+    /// filters, returning the files to search.
+    fn walk_files(&self) -> Result<Vec<PathBuf>, GrepError> {
+        let mut builder = WalkBuilder::new(&self.path);
+        builder
+            .hidden(!self.hidden)
+            .git_ignore(!self.no_ignore)
+            .git_global(!self.no_ignore)
+            .git_exclude(!self.no_ignore)
+            .ignore(!self.no_ignore);
+
+        if !self.type_matches.is_empty() || !self.type_not.is_empty() {
+            let mut types = TypesBuilder::new();
+            types.add_defaults();
+            for t in &self.type_matches {
+                types.select(t);
+            }
+            for t in &self.type_not {
+                types.negate(t);
+            }
+            builder.types(types.build()?);
+        }
+
+        if let Some(glob) = self.glob.as_deref() {
+            let mut overrides = OverrideBuilder::new(&self.path);
+            overrides.add(glob)?;
+            builder.overrides(overrides.build()?);
+        }
+
+        let mut files = Vec::new();
+        for entry in builder.build() {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    eprintln!("debug: skipping unreadable entry: {e}");
+                    continue;
+                }
+            };
+            if !entry.file_type().is_some_and(|ft| ft.is_file()) {
+                continue;
+            }
+            files.push(entry.into_path());
+        }
+        Ok(files)
+    }
+
     pub fn grep(&self) -> Result<(), GrepError> {
         self.grep_with(default_strategy)
     }
 
     pub fn grep_with(&self, strategy: StrategyFn) -> Result<(), GrepError> {
-        let regex = Regex::new(&self.pattern)?;
-        let files: Vec<_> = glob::glob(&self.glob)?.collect();
-        files.into_par_iter().for_each(|v| {
-            if let Ok(filename) = v {
-                if let Ok(file) = File::open(&filename) {
-                    let mut reader = BufReader::new(file);
-                    let mut stdout = io::stdout();
+        let matcher = self.matcher()?;
+        let context = self.context_lines();
+        let format = self.output_format();
+        let summary = self.summary_mode();
+        let files = self.walk_files()?;
+        let search_zip = self.search_zip;
+        let rga_accurate = self.rga_accurate;
+        let adapters = AdapterRegistry::new();
+        files.into_par_iter().for_each(|filename| {
+            let entries: Vec<(PathBuf, Box<dyn BufRead>)> = match adapters
+                .adapt(&filename, rga_accurate)
+            {
+                Ok(Some(entries)) => entries
+                    .into_iter()
+                    .map(|(p, bytes)| {
+                        (p, Box::new(BufReader::new(io::Cursor::new(bytes))) as Box<dyn BufRead>)
+                    })
+                    .collect(),
+                Ok(None) => {
+                    let Some(reader) = open_reader(&filename, search_zip) else {
+                        return;
+                    };
+                    vec![(filename.clone(), reader)]
+                }
+                Err(e) => {
+                    println!("Internal error: {:?}", e);
+                    return;
+                }
+            };
 
-                    if let Err(e) = strategy(filename.as_path(), &mut reader, &regex, &mut stdout) {
-                        println!("Internal error: {:?}", e);
-                    }
+            for (entry_path, mut reader) in entries {
+                let mut printer = Printer::new(format, summary);
+
+                if let Err(e) = strategy(
+                    entry_path.as_path(),
+                    reader.as_mut(),
+                    &matcher,
+                    &mut printer,
+                    context,
+                ) {
+                    println!("Internal error: {:?}", e);
+                    continue;
+                }
+                if let Some(out) = printer.finish() {
+                    let mut stdout = io::stdout();
+                    let _ = stdout.write_all(out.as_bytes());
                 }
             }
         });
@@ -63,103 +317,320 @@ impl GrepConfig {
         F: FnOnce(
                 PathBuf,
                 Pin<Box<dyn tokio::io::AsyncBufRead + 'a>>,
-                Regex,
-                Pin<Box<dyn tokio::io::AsyncWrite + 'a>>,
+                Matcher,
+                Printer,
+                ContextLines,
             ) -> Fut
             + Copy,
-        Fut: Future<Output = Result<(), GrepError>>,
+        Fut: Future<Output = Result<Printer, GrepError>>,
     {
-        let files: Vec<_> = glob::glob(&self.glob)?.collect();
-        let regex = Regex::new(&self.pattern).unwrap();
-
-        let mut stream = futures::stream::iter(
-            files
-                .into_iter()
-                .filter(|x| {
-                    if let Ok(path) = x {
-                        path.is_file()
-                    } else {
-                        false
+        let files = self.walk_files()?;
+        let matcher = self.matcher()?;
+        let context = self.context_lines();
+        let format = self.output_format();
+        let summary = self.summary_mode();
+        let search_zip = self.search_zip;
+        let rga_accurate = self.rga_accurate;
+        let adapters = AdapterRegistry::new();
+
+        let mut stream = futures::stream::iter(files.into_iter().map(|path| {
+            let adapters = &adapters;
+            async move {
+                match adapters.adapt(&path, rga_accurate) {
+                    Ok(Some(entries)) => (path, AdaptedSource::Entries(entries)),
+                    Ok(None) => {
+                        let reader = open_async_reader(&path, search_zip).await;
+                        (path, AdaptedSource::Reader(reader))
                     }
-                })
-                .map(|x| async move {
-                    let path = x.unwrap();
-                    let res = fs::File::open(&path).await.unwrap();
-                    (path, res)
-                }),
-        )
+                    Err(e) => (path, AdaptedSource::Error(e)),
+                }
+            }
+        }))
         .buffer_unordered(8);
 
-        while let Some(pair) = stream.next().await {
-            let reader = tokio::io::BufReader::new(pair.1);
-            let writer = tokio::io::stdout();
-            let regex = regex.clone();
-            let _ = strategy(pair.0, Box::pin(reader), regex, Box::pin(writer)).await;
+        while let Some((path, source)) = stream.next().await {
+            match source {
+                AdaptedSource::Entries(entries) => {
+                    for (entry_path, bytes) in entries {
+                        let matcher = matcher.clone();
+                        let printer = Printer::new(format, summary);
+                        let reader: Pin<Box<dyn tokio::io::AsyncBufRead>> =
+                            Box::pin(tokio::io::BufReader::new(io::Cursor::new(bytes)));
+                        if let Ok(mut printer) =
+                            strategy(entry_path, reader, matcher, printer, context).await
+                        {
+                            if let Some(out) = printer.finish() {
+                                let mut stdout = tokio::io::stdout();
+                                let _ = stdout.write_all(out.as_bytes()).await;
+                            }
+                        }
+                    }
+                }
+                AdaptedSource::Reader(Some(reader)) => {
+                    let matcher = matcher.clone();
+                    let printer = Printer::new(format, summary);
+                    if let Ok(mut printer) = strategy(path, reader, matcher, printer, context).await
+                    {
+                        if let Some(out) = printer.finish() {
+                            let mut stdout = tokio::io::stdout();
+                            let _ = stdout.write_all(out.as_bytes()).await;
+                        }
+                    }
+                }
+                AdaptedSource::Reader(None) => {}
+                AdaptedSource::Error(e) => println!("Internal error: {:?}", e),
+            }
         }
 
         Ok(())
     }
 }
 
+/// Map a file extension to the external decompressor that can read it on
+/// stdin, for `--search-zip`.
+fn decompressor_for(path: &Path) -> Option<(&'static str, &'static [&'static str])> {
+    let name = path.to_string_lossy();
+    if name.ends_with(".tar.gz") || name.ends_with(".tgz") || name.ends_with(".gz") {
+        Some(("gzip", &["-dc"]))
+    } else if name.ends_with(".bz2") {
+        Some(("bzip2", &["-dc"]))
+    } else if name.ends_with(".xz") {
+        Some(("xz", &["-dc"]))
+    } else if name.ends_with(".zst") || name.ends_with(".tzst") {
+        Some(("zstd", &["-dc"]))
+    } else {
+        None
+    }
+}
+
+/// Wraps a spawned decompressor's stdout together with its `Child` so the
+/// process is waited on (instead of left as a zombie) once the reader is
+/// dropped, rather than letting `Child` fall out of scope unwaited right
+/// after `spawn`.
+struct ReapingReader {
+    child: std::process::Child,
+    stdout: BufReader<std::process::ChildStdout>,
+}
+
+impl io::Read for ReapingReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.stdout.read(buf)
+    }
+}
+
+impl BufRead for ReapingReader {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.stdout.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.stdout.consume(amt)
+    }
+}
+
+impl Drop for ReapingReader {
+    fn drop(&mut self) {
+        let _ = self.child.wait();
+    }
+}
+
+/// Async counterpart of [`ReapingReader`]. `tokio::process::Child` reaps
+/// orphaned processes on a best-effort basis when dropped, but only once it
+/// is actually dropped — so the `Child` must be kept alive alongside its
+/// stdout, not discarded right after `spawn`.
+struct AsyncReapingReader {
+    #[allow(dead_code)]
+    child: tokio::process::Child,
+    stdout: tokio::io::BufReader<tokio::process::ChildStdout>,
+}
+
+impl AsyncRead for AsyncReapingReader {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        Pin::new(&mut this.stdout).poll_read(cx, buf)
+    }
+}
+
+impl AsyncBufRead for AsyncReapingReader {
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        let this = self.get_mut();
+        Pin::new(&mut this.stdout).poll_fill_buf(cx)
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        let this = self.get_mut();
+        Pin::new(&mut this.stdout).consume(amt)
+    }
+}
+
+/// Open `path` for searching, decompressing it on the fly via an external
+/// decompressor when `search_zip` is set and the extension is recognized.
+/// Falls back to reading the file uncompressed if the decompressor binary
+/// isn't installed.
+fn open_reader(path: &Path, search_zip: bool) -> Option<Box<dyn BufRead>> {
+    if search_zip {
+        if let Some((cmd, args)) = decompressor_for(path) {
+            if let Ok(file) = File::open(path) {
+                match std::process::Command::new(cmd)
+                    .args(args)
+                    .stdin(Stdio::from(file))
+                    .stdout(Stdio::piped())
+                    .spawn()
+                {
+                    Ok(mut child) => {
+                        let stdout = child.stdout.take().expect("child stdout was piped");
+                        return Some(Box::new(ReapingReader {
+                            child,
+                            stdout: BufReader::new(stdout),
+                        }));
+                    }
+                    Err(_) => eprintln!(
+                        "debug: `{cmd}` not found, searching {} uncompressed",
+                        path.display()
+                    ),
+                }
+            }
+        }
+    }
+    File::open(path)
+        .ok()
+        .map(|file| Box::new(BufReader::new(file)) as Box<dyn BufRead>)
+}
+
+/// Async counterpart of [`open_reader`], spawning the decompressor via
+/// `tokio::process::Command` and streaming its stdout.
+async fn open_async_reader(
+    path: &Path,
+    search_zip: bool,
+) -> Option<Pin<Box<dyn tokio::io::AsyncBufRead>>> {
+    if search_zip {
+        if let Some((cmd, args)) = decompressor_for(path) {
+            if let Ok(file) = File::open(path) {
+                match tokio::process::Command::new(cmd)
+                    .args(args)
+                    .stdin(Stdio::from(file))
+                    .stdout(Stdio::piped())
+                    .spawn()
+                {
+                    Ok(mut child) => {
+                        let stdout = child.stdout.take().expect("child stdout was piped");
+                        return Some(Box::pin(AsyncReapingReader {
+                            child,
+                            stdout: tokio::io::BufReader::new(stdout),
+                        }));
+                    }
+                    Err(_) => eprintln!(
+                        "debug: `{cmd}` not found, searching {} uncompressed",
+                        path.display()
+                    ),
+                }
+            }
+        }
+    }
+    fs::File::open(path)
+        .await
+        .ok()
+        .map(|file| Box::pin(tokio::io::BufReader::new(file)) as Pin<Box<dyn tokio::io::AsyncBufRead>>)
+}
+
 pub fn default_strategy(
     path: &Path,
     reader: &mut dyn BufRead,
-    pattern: &Regex,
-    writer: &mut dyn Write,
+    pattern: &Matcher,
+    printer: &mut Printer,
+    context: ContextLines,
 ) -> Result<(), GrepError> {
-    let matches: String = reader
-        .lines()
-        .enumerate()
-        .map(|(lineno, line)| {
-            line.ok()
-                .map(|line| {
-                    pattern
-                        .find(&line)
-                        .map(|m| format_line(&line, lineno + 1, m.range()))
-                })
-                .flatten()
-        })
-        .filter_map(|v| v.ok_or(()).ok())
-        .join("\n");
+    printer.begin(path);
+    let mut before_buf: VecDeque<(usize, String)> = VecDeque::with_capacity(context.before);
+    let mut after_remaining = 0;
+    let mut last_emitted: Option<usize> = None;
 
-    if !matches.is_empty() {
-        writer.write_all(path.display().to_string().green().as_bytes())?;
-        writer.write_all(b"\n")?;
-        writer.write_all(matches.as_bytes())?;
-        writer.write_all(b"\n")?;
+    let context_enabled = context.before > 0 || context.after > 0;
+    for (lineno, line) in reader.lines().enumerate().map(|(i, l)| (i + 1, l)) {
+        let line = line?;
+        let ranges = pattern.find_iter(&line);
+        if !ranges.is_empty() {
+            let window_start = lineno.saturating_sub(context.before);
+            let contiguous = last_emitted.is_some_and(|last| window_start <= last + 1);
+            if !contiguous && context_enabled {
+                printer.separator();
+            }
+            for (bl, btext) in before_buf.drain(..) {
+                printer.context_line(bl, &btext);
+            }
+            printer.match_line(lineno, &line, &ranges);
+            printer.record_occurrences(ranges.len());
+            last_emitted = Some(lineno);
+            after_remaining = context.after;
+        } else if after_remaining > 0 {
+            printer.context_line(lineno, &line);
+            after_remaining -= 1;
+            last_emitted = Some(lineno);
+        } else {
+            if before_buf.len() == context.before && context.before > 0 {
+                before_buf.pop_front();
+            }
+            if context.before > 0 {
+                before_buf.push_back((lineno, line));
+            }
+        }
     }
 
     Ok(())
 }
 
-pub async fn default_async_strategy<'a>(
+pub async fn default_async_strategy(
     path: PathBuf,
     reader: Pin<Box<dyn tokio::io::AsyncBufRead>>,
-    pattern: Regex,
-    mut writer: Pin<Box<dyn tokio::io::AsyncWrite + 'a>>,
-) -> Result<(), GrepError> {
+    pattern: Matcher,
+    mut printer: Printer,
+    context: ContextLines,
+) -> Result<Printer, GrepError> {
+    printer.begin(&path);
     let mut lines = reader.lines();
-    let mut lineno = 0;
+    let mut lineno: usize = 0;
 
-    let mut finds = Vec::new();
+    let mut before_buf: VecDeque<String> = VecDeque::with_capacity(context.before);
+    let mut after_remaining = 0;
+    let mut last_emitted: Option<usize> = None;
+
+    let context_enabled = context.before > 0 || context.after > 0;
     while let Some(line) = lines.next_line().await? {
         lineno += 1;
-        if let Some(f) = pattern.find(&line) {
-            finds.push(format_line(&line, lineno, f.range()))
+        let ranges = pattern.find_iter(&line);
+        if !ranges.is_empty() {
+            let window_start = lineno.saturating_sub(context.before);
+            let contiguous = last_emitted.is_some_and(|last| window_start <= last + 1);
+            if !contiguous && context_enabled {
+                printer.separator();
+            }
+            let first_before = lineno - before_buf.len();
+            for (i, btext) in before_buf.drain(..).enumerate() {
+                printer.context_line(first_before + i, &btext);
+            }
+            printer.match_line(lineno, &line, &ranges);
+            printer.record_occurrences(ranges.len());
+            last_emitted = Some(lineno);
+            after_remaining = context.after;
+        } else if after_remaining > 0 {
+            printer.context_line(lineno, &line);
+            after_remaining -= 1;
+            last_emitted = Some(lineno);
+        } else {
+            if before_buf.len() == context.before && context.before > 0 {
+                before_buf.pop_front();
+            }
+            if context.before > 0 {
+                before_buf.push_back(line);
+            }
         }
     }
-    let matches = finds.join("\n");
-
-    if !matches.is_empty() {
-        writer
-            .write_all(path.display().to_string().green().as_bytes())
-            .await?;
-        writer.write_all(b"\n").await?;
-        writer.write_all(matches.as_bytes()).await?;
-        writer.write_all(b"\n").await?;
-    }
 
-    Ok(())
+    Ok(printer)
 }
 
 pub fn format_line(line: &str, lineno: usize, range: Range<usize>) -> String {
@@ -175,6 +646,12 @@ pub fn format_line(line: &str, lineno: usize, range: Range<usize>) -> String {
     )
 }
 
+/// Format a non-matching context line (printed before/after a match) with a
+/// dimmed line number, mirroring grep's `-` separator for context lines.
+pub fn format_context_line(line: &str, lineno: usize) -> String {
+    format!("{0: >6}-{1}", lineno.to_string().dimmed(), line)
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -197,10 +674,17 @@ mod tests {
         let path = Path::new("src/main.rs");
         let input = b"hello world!\nbye world!";
         let mut reader = BufReader::new(&input[..]);
-        let pattern = Regex::new(r"wo\w+").unwrap();
-        let mut writer = Vec::new();
-        default_strategy(path, &mut reader, &pattern, &mut writer).unwrap();
-        let result = String::from_utf8(writer).unwrap();
+        let pattern = Matcher::Regex(Regex::new(r"wo\w+").unwrap());
+        let mut printer = Printer::new(OutputFormat::Text, SummaryMode::None);
+        default_strategy(
+            path,
+            &mut reader,
+            &pattern,
+            &mut printer,
+            ContextLines::default(),
+        )
+        .unwrap();
+        let result = printer.finish().unwrap();
         let expected = [
             String::from("src/main.rs"),
             format_line("hello world!", 1, 6..11),
@@ -210,21 +694,77 @@ mod tests {
         assert_eq!(result, expected.join("\n"));
     }
 
+    #[test]
+    fn default_strategy_should_merge_adjacent_context_windows() {
+        let path = Path::new("src/main.rs");
+        let input = b"a\nworld one\nb\nc\nworld two\nd";
+        let mut reader = BufReader::new(&input[..]);
+        let pattern = Matcher::Regex(Regex::new(r"wo\w+").unwrap());
+        let mut printer = Printer::new(OutputFormat::Text, SummaryMode::None);
+        let context = ContextLines {
+            before: 1,
+            after: 1,
+        };
+        default_strategy(path, &mut reader, &pattern, &mut printer, context).unwrap();
+        let result = printer.finish().unwrap();
+        let expected = [
+            String::from("src/main.rs"),
+            format_context_line("a", 1),
+            format_line("world one", 2, 0..5),
+            format_context_line("b", 3),
+            format_context_line("c", 4),
+            format_line("world two", 5, 0..5),
+            format_context_line("d", 6),
+        ];
+
+        assert_eq!(result, format!("{}\n", expected.join("\n")));
+    }
+
+    #[test]
+    fn default_strategy_should_not_emit_separator_without_context() {
+        let path = Path::new("src/main.rs");
+        let input = b"world one\nbye\nbye\nbye\nworld two";
+        let mut reader = BufReader::new(&input[..]);
+        let pattern = Matcher::Regex(Regex::new(r"wo\w+").unwrap());
+        let mut printer = Printer::new(OutputFormat::Text, SummaryMode::None);
+        default_strategy(
+            path,
+            &mut reader,
+            &pattern,
+            &mut printer,
+            ContextLines::default(),
+        )
+        .unwrap();
+        let result = printer.finish().unwrap();
+        let expected = [
+            String::from("src/main.rs"),
+            format_line("world one", 1, 0..5),
+            format_line("world two", 5, 0..5),
+        ];
+
+        assert_eq!(result, format!("{}\n", expected.join("\n")));
+        assert!(!result.contains("--"));
+    }
+
     #[tokio::test]
     async fn default_async_strategy_should_work() {
         let path = Path::new("src/main.rs");
         let input = b"hello world!\nbye world!";
         let reader = tokio::io::BufReader::new(&input[..]);
-        let pattern = Regex::new(r"wo\w+").unwrap();
-
-        let mut writer = Vec::new();
-        let pin_writer = Box::pin(&mut writer);
+        let pattern = Matcher::Regex(Regex::new(r"wo\w+").unwrap());
+        let printer = Printer::new(OutputFormat::Text, SummaryMode::None);
 
-        default_async_strategy(path.to_path_buf(), Box::pin(reader), pattern, pin_writer)
-            .await
-            .unwrap();
+        let mut printer = default_async_strategy(
+            path.to_path_buf(),
+            Box::pin(reader),
+            pattern,
+            printer,
+            ContextLines::default(),
+        )
+        .await
+        .unwrap();
 
-        let result = String::from_utf8(writer).unwrap();
+        let result = printer.finish().unwrap();
         let expected = [
             String::from("src/main.rs"),
             format_line("hello world!", 1, 6..11),
@@ -233,4 +773,182 @@ mod tests {
 
         assert_eq!(result, expected.join("\n"));
     }
+
+    #[test]
+    fn json_printer_should_emit_ndjson_events() {
+        let path = Path::new("src/main.rs");
+        let input = b"hello world!\nbye world!";
+        let mut reader = BufReader::new(&input[..]);
+        let pattern = Matcher::Regex(Regex::new(r"wo\w+").unwrap());
+        let mut printer = Printer::new(OutputFormat::Json, SummaryMode::None);
+        default_strategy(
+            path,
+            &mut reader,
+            &pattern,
+            &mut printer,
+            ContextLines::default(),
+        )
+        .unwrap();
+        let result = printer.finish().unwrap();
+        let events: Vec<&str> = result.trim_end().split('\n').collect();
+
+        assert_eq!(events.len(), 4);
+        assert!(events[0].contains(r#""type":"begin""#));
+        assert!(events[1].contains(r#""type":"match""#) && events[1].contains(r#""line_number":1"#));
+        assert!(events[2].contains(r#""type":"match""#) && events[2].contains(r#""line_number":2"#));
+        assert!(events[3].contains(r#""type":"end""#) && events[3].contains(r#""matches":2"#));
+    }
+
+    #[test]
+    fn json_printer_should_emit_nothing_for_a_file_with_no_matches() {
+        let path = Path::new("src/main.rs");
+        let input = b"hello\nbye";
+        let mut reader = BufReader::new(&input[..]);
+        let pattern = Matcher::Regex(Regex::new(r"wo\w+").unwrap());
+        let mut printer = Printer::new(OutputFormat::Json, SummaryMode::None);
+        default_strategy(
+            path,
+            &mut reader,
+            &pattern,
+            &mut printer,
+            ContextLines::default(),
+        )
+        .unwrap();
+
+        assert!(printer.finish().is_none());
+    }
+
+    #[test]
+    fn json_printer_should_emit_one_submatch_per_occurrence_on_a_line() {
+        let path = Path::new("src/main.rs");
+        let input = b"world world";
+        let mut reader = BufReader::new(&input[..]);
+        let pattern = Matcher::Regex(Regex::new(r"wo\w+").unwrap());
+        let mut printer = Printer::new(OutputFormat::Json, SummaryMode::None);
+        default_strategy(
+            path,
+            &mut reader,
+            &pattern,
+            &mut printer,
+            ContextLines::default(),
+        )
+        .unwrap();
+        let result = printer.finish().unwrap();
+        let events: Vec<&str> = result.trim_end().split('\n').collect();
+
+        assert_eq!(events.len(), 3);
+        let match_event: serde_json::Value = serde_json::from_str(events[1]).unwrap();
+        let submatches = match_event["submatches"].as_array().unwrap();
+        assert_eq!(submatches.len(), 2);
+        assert_eq!(submatches[0]["start"], 0);
+        assert_eq!(submatches[0]["end"], 5);
+        assert_eq!(submatches[1]["start"], 6);
+        assert_eq!(submatches[1]["end"], 11);
+    }
+
+    #[test]
+    fn count_summary_mode_should_report_matching_line_count() {
+        let path = Path::new("src/main.rs");
+        let input = b"world world\nbye\nworld";
+        let mut reader = BufReader::new(&input[..]);
+        let pattern = Matcher::Regex(Regex::new(r"wo\w+").unwrap());
+        let mut printer = Printer::new(OutputFormat::Text, SummaryMode::Count);
+        default_strategy(
+            path,
+            &mut reader,
+            &pattern,
+            &mut printer,
+            ContextLines::default(),
+        )
+        .unwrap();
+        let result = printer.finish().unwrap();
+
+        assert_eq!(result, "src/main.rs:2\n");
+    }
+
+    #[test]
+    fn count_matches_summary_mode_should_count_every_occurrence_on_a_line() {
+        let path = Path::new("src/main.rs");
+        let input = b"world world\nbye\nworld";
+        let mut reader = BufReader::new(&input[..]);
+        let pattern = Matcher::Regex(Regex::new(r"wo\w+").unwrap());
+        let mut printer = Printer::new(OutputFormat::Text, SummaryMode::CountMatches);
+        default_strategy(
+            path,
+            &mut reader,
+            &pattern,
+            &mut printer,
+            ContextLines::default(),
+        )
+        .unwrap();
+        let result = printer.finish().unwrap();
+
+        assert_eq!(result, "src/main.rs:3\n");
+    }
+
+    #[test]
+    fn files_with_matches_summary_mode_should_print_only_the_path() {
+        let path = Path::new("src/main.rs");
+        let input = b"world\nbye";
+        let mut reader = BufReader::new(&input[..]);
+        let pattern = Matcher::Regex(Regex::new(r"wo\w+").unwrap());
+        let mut printer = Printer::new(OutputFormat::Text, SummaryMode::FilesWithMatches);
+        default_strategy(
+            path,
+            &mut reader,
+            &pattern,
+            &mut printer,
+            ContextLines::default(),
+        )
+        .unwrap();
+        let result = printer.finish().unwrap();
+
+        assert_eq!(result, "src/main.rs\n");
+    }
+
+    #[test]
+    fn files_with_matches_summary_mode_should_omit_files_without_a_match() {
+        let path = Path::new("src/main.rs");
+        let input = b"bye";
+        let mut reader = BufReader::new(&input[..]);
+        let pattern = Matcher::Regex(Regex::new(r"wo\w+").unwrap());
+        let mut printer = Printer::new(OutputFormat::Text, SummaryMode::FilesWithMatches);
+        default_strategy(
+            path,
+            &mut reader,
+            &pattern,
+            &mut printer,
+            ContextLines::default(),
+        )
+        .unwrap();
+
+        assert!(printer.finish().is_none());
+    }
+
+    #[test]
+    fn adapter_registry_should_expand_zip_entries_into_virtual_sub_paths() {
+        use std::io::Write;
+
+        let mut zip_bytes = Vec::new();
+        {
+            let mut writer = zip::ZipWriter::new(io::Cursor::new(&mut zip_bytes));
+            writer
+                .start_file("inner.txt", zip::write::FileOptions::default())
+                .unwrap();
+            writer.write_all(b"hello world!").unwrap();
+            writer.finish().unwrap();
+        }
+        let zip_path =
+            std::env::temp_dir().join(format!("mini_grep_test_{}.zip", std::process::id()));
+        std::fs::write(&zip_path, &zip_bytes).unwrap();
+
+        let registry = AdapterRegistry::new();
+        let entries = registry.adapt(&zip_path, false).unwrap().unwrap();
+        std::fs::remove_file(&zip_path).unwrap();
+
+        assert_eq!(entries.len(), 1);
+        let (path, contents) = &entries[0];
+        assert_eq!(path, &zip_path.join("inner.txt"));
+        assert_eq!(contents, b"hello world!");
+    }
 }
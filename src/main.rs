@@ -9,7 +9,7 @@ async fn main() -> Result<()> {
     // config.grep()?;
     //
     // config
-    //     .async_grep_with(|path, _, pattern, _| async move {
+    //     .async_grep_with(|path, _, pattern, _, _| async move {
     //         println!(
     //             "path: {} pattern: {}",
     //             path.as_path().to_str().unwrap(),
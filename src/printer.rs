@@ -0,0 +1,235 @@
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use colored::*;
+use serde::Serialize;
+use std::{
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use crate::{format_context_line, format_line};
+
+/// Output format selected via `GrepConfig`'s `--json` flag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human-readable, colored terminal output (the default).
+    Text,
+    /// Newline-delimited JSON, one event object per line.
+    Json,
+}
+
+/// Aggregate output mode selected via `GrepConfig`'s `--count`,
+/// `--count-matches`, and `--files-with-matches` flags. Only affects
+/// `OutputFormat::Text`; JSON output always reports full per-match detail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SummaryMode {
+    /// Print every matching line, as usual.
+    #[default]
+    None,
+    /// Print `path:N`, the number of matching lines.
+    Count,
+    /// Print `path:N`, the total number of match occurrences.
+    CountMatches,
+    /// Print only the path, if it contains at least one match.
+    FilesWithMatches,
+}
+
+/// Either valid UTF-8 text or, when the underlying bytes are not valid
+/// UTF-8, their base64 encoding. Keeps JSON output well-formed even when a
+/// match comes from a lossily-decoded source (e.g. a decompressed or
+/// binary-adapted file).
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum JsonData {
+    Text { text: String },
+    Bytes { bytes: String },
+}
+
+impl JsonData {
+    fn from_bytes(bytes: &[u8]) -> Self {
+        match std::str::from_utf8(bytes) {
+            Ok(text) => JsonData::Text {
+                text: text.to_string(),
+            },
+            Err(_) => JsonData::Bytes {
+                bytes: STANDARD.encode(bytes),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct SubMatch {
+    #[serde(rename = "match")]
+    matched: JsonData,
+    start: usize,
+    end: usize,
+}
+
+#[derive(Debug, Serialize, Default)]
+struct Stats {
+    matches: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum Event<'a> {
+    Begin {
+        path: &'a str,
+    },
+    Match {
+        path: &'a str,
+        line_number: usize,
+        lines: JsonData,
+        submatches: Vec<SubMatch>,
+    },
+    End {
+        path: &'a str,
+        stats: Stats,
+    },
+}
+
+/// Collects the events produced while scanning a single file and renders
+/// them as either the colored human format or JSON Lines, depending on
+/// `format`. `default_strategy`/`default_async_strategy` report matches
+/// through this type instead of writing directly, so they stay agnostic of
+/// the chosen output format.
+pub struct Printer {
+    format: OutputFormat,
+    summary: SummaryMode,
+    path: PathBuf,
+    lines: Vec<String>,
+    matches: usize,
+    occurrences: usize,
+    printed_any_block: bool,
+}
+
+impl Printer {
+    pub fn new(format: OutputFormat, summary: SummaryMode) -> Self {
+        Self {
+            format,
+            summary,
+            path: PathBuf::new(),
+            lines: Vec::new(),
+            matches: 0,
+            occurrences: 0,
+            printed_any_block: false,
+        }
+    }
+
+    /// Start scanning a new file.
+    pub fn begin(&mut self, path: &Path) {
+        self.path = path.to_path_buf();
+        self.lines.clear();
+        self.matches = 0;
+        self.occurrences = 0;
+        self.printed_any_block = false;
+    }
+
+    /// Report a matching line, along with every occurrence range on it
+    /// (`ranges` must be non-empty). Text mode highlights only the first
+    /// occurrence; JSON mode reports one submatch per occurrence.
+    pub fn match_line(&mut self, lineno: usize, line: &str, ranges: &[Range<usize>]) {
+        // Only emit `begin` once the file turns out to have a match, so
+        // non-matching files don't flood JSON output with empty begin/end
+        // pairs.
+        if self.format == OutputFormat::Json && self.matches == 0 {
+            self.lines.push(json_line(&Event::Begin {
+                path: &self.path.display().to_string(),
+            }));
+        }
+        self.matches += 1;
+        match self.format {
+            OutputFormat::Text => {
+                if self.summary == SummaryMode::None {
+                    self.lines.push(format_line(line, lineno, ranges[0].clone()));
+                    self.printed_any_block = true;
+                }
+            }
+            OutputFormat::Json => {
+                let submatches = ranges
+                    .iter()
+                    .map(|range| SubMatch {
+                        matched: JsonData::from_bytes(&line.as_bytes()[range.start..range.end]),
+                        start: range.start,
+                        end: range.end,
+                    })
+                    .collect();
+                self.lines.push(json_line(&Event::Match {
+                    path: &self.path.display().to_string(),
+                    line_number: lineno,
+                    lines: JsonData::from_bytes(line.as_bytes()),
+                    submatches,
+                }));
+            }
+        }
+    }
+
+    /// Record `n` additional match occurrences on the line just reported via
+    /// `match_line`, for `--count-matches` (which counts every hit on a line,
+    /// not just the lines that contain one).
+    pub fn record_occurrences(&mut self, n: usize) {
+        self.occurrences += n;
+    }
+
+    pub fn context_line(&mut self, lineno: usize, line: &str) {
+        if self.format == OutputFormat::Text && self.summary == SummaryMode::None {
+            self.lines.push(format_context_line(line, lineno));
+        }
+    }
+
+    /// Marks the boundary between two non-contiguous context blocks within
+    /// the same file. A no-op before the first block, in JSON mode, or in a
+    /// summary mode.
+    pub fn separator(&mut self) {
+        if self.format == OutputFormat::Text
+            && self.summary == SummaryMode::None
+            && self.printed_any_block
+        {
+            self.lines.push("--".to_string());
+        }
+    }
+
+    /// Finish scanning the current file, returning the rendered output (if
+    /// any) to be written out by the caller.
+    pub fn finish(&mut self) -> Option<String> {
+        match self.format {
+            OutputFormat::Text => match self.summary {
+                SummaryMode::None => {
+                    if self.lines.is_empty() {
+                        None
+                    } else {
+                        Some(format!(
+                            "{}\n{}\n",
+                            self.path.display().to_string().green(),
+                            self.lines.join("\n")
+                        ))
+                    }
+                }
+                SummaryMode::FilesWithMatches => (self.matches > 0)
+                    .then(|| format!("{}\n", self.path.display())),
+                SummaryMode::Count => {
+                    (self.matches > 0).then(|| format!("{}:{}\n", self.path.display(), self.matches))
+                }
+                SummaryMode::CountMatches => (self.occurrences > 0)
+                    .then(|| format!("{}:{}\n", self.path.display(), self.occurrences)),
+            },
+            OutputFormat::Json => {
+                if self.matches == 0 {
+                    None
+                } else {
+                    self.lines.push(json_line(&Event::End {
+                        path: &self.path.display().to_string(),
+                        stats: Stats {
+                            matches: self.matches,
+                        },
+                    }));
+                    Some(format!("{}\n", self.lines.join("\n")))
+                }
+            }
+        }
+    }
+}
+
+fn json_line(event: &Event) -> String {
+    serde_json::to_string(event).unwrap_or_default()
+}